@@ -1,9 +1,46 @@
 //! Provides an interface for building activities to send
 //! to Discord via [`DiscordIpc::set_activity`](crate::DiscordIpc::set_activity).
 
+use std::fmt;
+
+use bitflags::bitflags;
 use serde_derive::Serialize;
 use serde_repr::Serialize_repr;
 
+/// Errors returned by the `try_*` builders on [`Activity`] and [`Button`]
+/// when a field violates one of Discord's documented limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivityError {
+    /// A button label was empty or longer than 32 characters
+    ButtonLabelLength(usize),
+    /// A button URL was empty or longer than 512 characters
+    ButtonUrlLength(usize),
+    /// More than 2 buttons were provided
+    TooManyButtons(usize),
+    /// `state` was empty or longer than 128 characters
+    StateLength(usize),
+    /// `details` was empty or longer than 128 characters
+    DetailsLength(usize),
+}
+impl fmt::Display for ActivityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ButtonLabelLength(len) => {
+                write!(f, "button label must be 1-32 characters, got {len}")
+            }
+            Self::ButtonUrlLength(len) => {
+                write!(f, "button URL must be 1-512 characters, got {len}")
+            }
+            Self::TooManyButtons(len) => {
+                write!(f, "an activity may have at most 2 buttons, got {len}")
+            }
+            Self::StateLength(len) => write!(f, "state must be 1-128 characters, got {len}"),
+            Self::DetailsLength(len) => write!(f, "details must be 1-128 characters, got {len}"),
+        }
+    }
+}
+impl std::error::Error for ActivityError {}
+
 /// A struct representing a Discord rich presence activity.
 ///
 /// See [Activity Structure](https://discord.com/developers/docs/events/gateway-events#activity-object-activity-structure).
@@ -14,6 +51,8 @@ pub struct Activity {
     pub state: Option<String>,
     /// Details about the player in the activity
     pub details: Option<String>,
+    /// Stream URL, required when `activity_type` is [`ActivityType::Streaming`]
+    pub url: Option<String>,
     /// Timestamps for the activity
     pub timestamps: Option<Timestamps>,
     /// Information about the current party of the player
@@ -24,6 +63,12 @@ pub struct Activity {
     pub secrets: Option<Secrets>,
     /// Button(s) settings for the Activity (max: 2)
     pub buttons: Option<Vec<Button>>,
+    /// Emoji used for a custom status, see [`ActivityType::Custom`]
+    pub emoji: Option<CustomEmoji>,
+    /// Whether this activity is an instanced game session
+    pub instance: Option<bool>,
+    /// Bitfield of flags describing what an activity payload includes
+    pub flags: Option<ActivityFlags>,
     #[serde(rename = "type")]
     /// Activity type setting
     pub activity_type: Option<ActivityType>,
@@ -46,30 +91,95 @@ impl Activity {
         self
     }
 
+    /// Sets the state of the activity, validating that it is 1-128 characters long.
+    pub fn try_state(mut self, state: String) -> Result<Self, ActivityError> {
+        let len = state.chars().count();
+        if len == 0 || len > 128 {
+            return Err(ActivityError::StateLength(len));
+        }
+        self.state = Some(state);
+        Ok(self)
+    }
+
+    /// Sets the details of the activity, validating that it is 1-128 characters long.
+    pub fn try_details(mut self, details: String) -> Result<Self, ActivityError> {
+        let len = details.chars().count();
+        if len == 0 || len > 128 {
+            return Err(ActivityError::DetailsLength(len));
+        }
+        self.details = Some(details);
+        Ok(self)
+    }
+
+    /// Sets the stream URL of the activity.
+    ///
+    /// Required for Discord to render "Live on X" when `activity_type`
+    /// is [`ActivityType::Streaming`]; must be a valid Twitch or YouTube URL.
+    pub fn url(mut self, url: String) -> Self {
+        self.url = Some(url);
+        self
+    }
+
     /// Add a `Timestamps` to this activity.
     pub fn timestamps(mut self, timestamps: Timestamps) -> Self {
         self.timestamps = Some(timestamps);
         self
     }
 
+    /// Build and add a `Timestamps` to this activity via a closure, e.g.
+    /// `Activity::new().with_timestamps(|t| t.start(123).end(456))`.
+    pub fn with_timestamps<F>(self, f: F) -> Self
+    where
+        F: FnOnce(Timestamps) -> Timestamps,
+    {
+        self.timestamps(f(Timestamps::new()))
+    }
+
     /// Add a `Party` to this activity.
     pub fn party(mut self, party: Party) -> Self {
         self.party = Some(party);
         self
     }
 
+    /// Build and add a `Party` to this activity via a closure, e.g.
+    /// `Activity::new().with_party(|p| p.id("1234".to_string()))`.
+    pub fn with_party<F>(self, f: F) -> Self
+    where
+        F: FnOnce(Party) -> Party,
+    {
+        self.party(f(Party::new()))
+    }
+
     /// Add an `Assets` to this activity.
     pub fn assets(mut self, assets: Assets) -> Self {
         self.assets = Some(assets);
         self
     }
 
+    /// Build and add an `Assets` to this activity via a closure, e.g.
+    /// `Activity::new().with_assets(|a| a.large_image("img".to_string()))`.
+    pub fn with_assets<F>(self, f: F) -> Self
+    where
+        F: FnOnce(Assets) -> Assets,
+    {
+        self.assets(f(Assets::new()))
+    }
+
     /// Add a `Secrets` to this activity.
     pub fn secrets(mut self, secrets: Secrets) -> Self {
         self.secrets = Some(secrets);
         self
     }
 
+    /// Build and add a `Secrets` to this activity via a closure, e.g.
+    /// `Activity::new().with_secrets(|s| s.join("secret".to_string()))`.
+    pub fn with_secrets<F>(self, f: F) -> Self
+    where
+        F: FnOnce(Secrets) -> Secrets,
+    {
+        self.secrets(f(Secrets::new()))
+    }
+
     /// Add a `Vec` of `Button`s to this activity.
     ///
     /// An activity may contain no more than 2 buttons
@@ -84,11 +194,52 @@ impl Activity {
         self
     }
 
+    /// Add a `Vec` of `Button`s to this activity, validating that there are
+    /// no more than 2.
+    pub fn try_buttons(mut self, buttons: Vec<Button>) -> Result<Self, ActivityError> {
+        if buttons.len() > 2 {
+            return Err(ActivityError::TooManyButtons(buttons.len()));
+        }
+
+        // API call fails if the array is empty, so we skip serialization
+        // entirely if this is the case
+        if buttons.is_empty() {
+            return Ok(self);
+        }
+
+        self.buttons = Some(buttons);
+        Ok(self)
+    }
+
     /// Add an `ActivityType` to this activity.
     pub fn activity_type(mut self, activity_type: ActivityType) -> Self {
         self.activity_type = Some(activity_type);
         self
     }
+
+    /// Add a `CustomEmoji` to this activity.
+    ///
+    /// Used alongside [`ActivityType::Custom`] to show an emoji
+    /// next to a custom status line.
+    pub fn emoji(mut self, emoji: CustomEmoji) -> Self {
+        self.emoji = Some(emoji);
+        self
+    }
+
+    /// Sets whether this activity is an instanced game session.
+    ///
+    /// Should be set alongside [`Secrets::join`] or [`Secrets::spectate`]
+    /// for join/spectate to behave correctly.
+    pub fn instance(mut self, instance: bool) -> Self {
+        self.instance = Some(instance);
+        self
+    }
+
+    /// Sets the `ActivityFlags` bitfield of this activity.
+    pub fn flags(mut self, flags: ActivityFlags) -> Self {
+        self.flags = Some(flags);
+        self
+    }
 }
 
 /// A struct representing an `Activity`'s timestamps.
@@ -259,6 +410,92 @@ impl Button {
     pub fn new(label: String, url: String) -> Self {
         Button { label, url }
     }
+
+    /// Creates a new `Button`, validating that the label is 1-32 characters
+    /// long and the URL is 1-512 characters long.
+    pub fn try_new(label: String, url: String) -> Result<Self, ActivityError> {
+        let label_len = label.chars().count();
+        if label_len == 0 || label_len > 32 {
+            return Err(ActivityError::ButtonLabelLength(label_len));
+        }
+
+        let url_len = url.chars().count();
+        if url_len == 0 || url_len > 512 {
+            return Err(ActivityError::ButtonUrlLength(url_len));
+        }
+
+        Ok(Button { label, url })
+    }
+}
+
+/// A struct representing the emoji used by a custom status `Activity`.
+///
+/// See [Activity Emoji](https://discord.com/developers/docs/events/gateway-events#activity-object-activity-emoji).
+#[serde_with::skip_serializing_none]
+#[derive(Serialize, Clone, Default)]
+pub struct CustomEmoji {
+    /// Name of the emoji
+    pub name: String,
+    /// ID of the emoji, if it is a custom guild emoji
+    pub id: Option<String>,
+    /// Whether the emoji is animated
+    pub animated: Option<bool>,
+}
+impl CustomEmoji {
+    /// Creates a new `CustomEmoji` with the given name.
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the ID of the emoji.
+    pub fn id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets whether the emoji is animated.
+    pub fn animated(mut self, animated: bool) -> Self {
+        self.animated = Some(animated);
+        self
+    }
+}
+
+bitflags! {
+    /// Bitfield of flags describing what an `Activity` payload includes.
+    ///
+    /// See [Activity Flags](https://discord.com/developers/docs/events/gateway-events#activity-object-activity-flags).
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    pub struct ActivityFlags: i32 {
+        /// The activity is an instanced game session
+        const INSTANCE = 1 << 0;
+        /// The activity supports joining
+        const JOIN = 1 << 1;
+        /// The activity supports spectating
+        const SPECTATE = 1 << 2;
+        /// The activity supports join requests
+        const JOIN_REQUEST = 1 << 3;
+        /// The activity is synced
+        const SYNC = 1 << 4;
+        /// The activity is being played
+        const PLAY = 1 << 5;
+        /// The activity's party privacy is set to "Friends"
+        const PARTY_PRIVACY_FRIENDS = 1 << 6;
+        /// The activity's party privacy is set to "Voice Channel"
+        const PARTY_PRIVACY_VOICE_CHANNEL = 1 << 7;
+        /// The activity is embedded
+        const EMBEDDED = 1 << 8;
+    }
+}
+impl serde::Serialize for ActivityFlags {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_i32(self.bits())
+    }
 }
 
 /// An enum representing the Activity Type of the `Activity`
@@ -267,10 +504,112 @@ impl Button {
 pub enum ActivityType {
     /// Activity type "Playing X"
     Playing = 0,
+    /// Activity type "Live on X"
+    ///
+    /// Requires [`Activity::url`] to be set to a valid Twitch or YouTube URL.
+    Streaming = 1,
     /// Activity type "Listening to X"
     Listening = 2,
     /// Activity type "Watching X"
     Watching = 3,
+    /// Activity type for a custom status, see [`Activity::emoji`] and [`Activity::state`]
+    Custom = 4,
     /// Activity type "Competing in X"
     Competing = 5,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_of_len(len: usize) -> String {
+        "a".repeat(len)
+    }
+
+    #[test]
+    fn try_state_accepts_boundary_lengths() {
+        assert!(Activity::new().try_state(string_of_len(1)).is_ok());
+        assert!(Activity::new().try_state(string_of_len(128)).is_ok());
+    }
+
+    #[test]
+    fn try_state_rejects_out_of_bounds_lengths() {
+        assert_eq!(
+            Activity::new().try_state(string_of_len(0)).err(),
+            Some(ActivityError::StateLength(0))
+        );
+        assert_eq!(
+            Activity::new().try_state(string_of_len(129)).err(),
+            Some(ActivityError::StateLength(129))
+        );
+    }
+
+    #[test]
+    fn try_details_accepts_boundary_lengths() {
+        assert!(Activity::new().try_details(string_of_len(1)).is_ok());
+        assert!(Activity::new().try_details(string_of_len(128)).is_ok());
+    }
+
+    #[test]
+    fn try_details_rejects_out_of_bounds_lengths() {
+        assert_eq!(
+            Activity::new().try_details(string_of_len(0)).err(),
+            Some(ActivityError::DetailsLength(0))
+        );
+        assert_eq!(
+            Activity::new().try_details(string_of_len(129)).err(),
+            Some(ActivityError::DetailsLength(129))
+        );
+    }
+
+    #[test]
+    fn button_try_new_accepts_boundary_lengths() {
+        assert!(Button::try_new(string_of_len(1), string_of_len(1)).is_ok());
+        assert!(Button::try_new(string_of_len(32), string_of_len(512)).is_ok());
+    }
+
+    #[test]
+    fn button_try_new_rejects_out_of_bounds_label() {
+        assert_eq!(
+            Button::try_new(string_of_len(0), string_of_len(1)).err(),
+            Some(ActivityError::ButtonLabelLength(0))
+        );
+        assert_eq!(
+            Button::try_new(string_of_len(33), string_of_len(1)).err(),
+            Some(ActivityError::ButtonLabelLength(33))
+        );
+    }
+
+    #[test]
+    fn button_try_new_rejects_out_of_bounds_url() {
+        assert_eq!(
+            Button::try_new(string_of_len(1), string_of_len(0)).err(),
+            Some(ActivityError::ButtonUrlLength(0))
+        );
+        assert_eq!(
+            Button::try_new(string_of_len(1), string_of_len(513)).err(),
+            Some(ActivityError::ButtonUrlLength(513))
+        );
+    }
+
+    #[test]
+    fn try_buttons_accepts_up_to_two() {
+        let button = Button::new(string_of_len(1), string_of_len(1));
+        assert!(Activity::new().try_buttons(vec![]).is_ok());
+        assert!(Activity::new().try_buttons(vec![button.clone()]).is_ok());
+        assert!(Activity::new()
+            .try_buttons(vec![button.clone(), button.clone()])
+            .is_ok());
+    }
+
+    #[test]
+    fn try_buttons_rejects_more_than_two() {
+        let button = Button::new(string_of_len(1), string_of_len(1));
+        assert_eq!(
+            Activity::new()
+                .try_buttons(vec![button.clone(), button.clone(), button])
+                .err(),
+            Some(ActivityError::TooManyButtons(3))
+        );
+    }
+}
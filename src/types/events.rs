@@ -0,0 +1,157 @@
+//! Typed payloads for the activity invite events Discord dispatches back
+//! over the IPC socket, and a [`DiscordIpc`] extension to subscribe to them.
+//!
+//! These events turn [`Secrets`](crate::types::activity::Secrets) from a
+//! one-way presence field into the full join/spectate flow: a user clicks
+//! "Ask to Join" or "Spectate" in the Discord client, Discord dispatches
+//! `ACTIVITY_JOIN`/`ACTIVITY_SPECTATE`/`ACTIVITY_JOIN_REQUEST` back over the
+//! socket, and the game decides whether to let the requester in.
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::DiscordIpc;
+
+/// Name of the RPC event dispatched when a user accepts a "join" invite.
+pub const ACTIVITY_JOIN: &str = "ACTIVITY_JOIN";
+/// Name of the RPC event dispatched when a user accepts a "spectate" invite.
+pub const ACTIVITY_SPECTATE: &str = "ACTIVITY_SPECTATE";
+/// Name of the RPC event dispatched when another user asks to join.
+pub const ACTIVITY_JOIN_REQUEST: &str = "ACTIVITY_JOIN_REQUEST";
+
+/// A partial Discord user, as sent with an [`ActivityJoinRequestEvent`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PartialUser {
+    /// The user's ID
+    pub id: String,
+    /// The user's username
+    pub username: String,
+    /// The user's 4-digit discord-tag
+    pub discriminator: String,
+    /// The user's avatar hash, if they have one set
+    pub avatar: Option<String>,
+}
+
+/// Dispatched when the local user accepts an invite to join a party.
+///
+/// See [Activity Join](https://discord.com/developers/docs/topics/rpc#activityjoin).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ActivityJoinEvent {
+    /// The `join` secret from the inviting party's `Secrets`
+    pub secret: String,
+}
+
+/// Dispatched when the local user accepts an invite to spectate a match.
+///
+/// See [Activity Spectate](https://discord.com/developers/docs/topics/rpc#activityspectate).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ActivitySpectateEvent {
+    /// The `spectate` secret from the match being spectated
+    pub secret: String,
+}
+
+/// Dispatched when another user asks to join the local user's party.
+///
+/// See [Activity Join Request](https://discord.com/developers/docs/topics/rpc#activityjoinrequest).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ActivityJoinRequestEvent {
+    /// The user requesting to join
+    pub user: PartialUser,
+}
+
+/// A typed activity event received off the IPC socket, as returned by
+/// [`ActivityEvents::try_next_activity_event`].
+pub enum ActivityEvent {
+    /// See [`ActivityJoinEvent`]
+    Join(ActivityJoinEvent),
+    /// See [`ActivitySpectateEvent`]
+    Spectate(ActivitySpectateEvent),
+    /// See [`ActivityJoinRequestEvent`]
+    JoinRequest(ActivityJoinRequestEvent),
+}
+
+/// Extends [`DiscordIpc`] with the join/spectate invite subsystem built on
+/// top of activity [`Secrets`](crate::types::activity::Secrets).
+pub trait ActivityEvents: DiscordIpc {
+    /// Subscribes to `ACTIVITY_JOIN`, fired when the local user accepts a
+    /// join invite from [`Secrets::join`](crate::types::activity::Secrets::join).
+    fn subscribe_activity_join(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.subscribe(ACTIVITY_JOIN)
+    }
+
+    /// Subscribes to `ACTIVITY_SPECTATE`, fired when the local user accepts
+    /// a spectate invite from [`Secrets::spectate`](crate::types::activity::Secrets::spectate).
+    fn subscribe_activity_spectate(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.subscribe(ACTIVITY_SPECTATE)
+    }
+
+    /// Subscribes to `ACTIVITY_JOIN_REQUEST`, fired when another user asks
+    /// to join the local user's party.
+    fn subscribe_activity_join_request(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.subscribe(ACTIVITY_JOIN_REQUEST)
+    }
+
+    /// Sends the `SUBSCRIBE` RPC command for the given event name.
+    fn subscribe(&mut self, evt: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.send(
+            json!({
+                "cmd": "SUBSCRIBE",
+                "evt": evt,
+                "nonce": Uuid::new_v4().to_string(),
+            }),
+            1,
+        )
+    }
+
+    /// Sends a join invite to the given user, prompting them to join the
+    /// local user's party.
+    fn send_join_invite(&mut self, user_id: impl Into<String>) -> Result<(), Box<dyn std::error::Error>> {
+        self.send(
+            json!({
+                "cmd": "SEND_ACTIVITY_JOIN_INVITE",
+                "args": { "user_id": user_id.into() },
+                "nonce": Uuid::new_v4().to_string(),
+            }),
+            1,
+        )
+    }
+
+    /// Rejects a pending join request from the given user.
+    fn close_join_request(&mut self, user_id: impl Into<String>) -> Result<(), Box<dyn std::error::Error>> {
+        self.send(
+            json!({
+                "cmd": "CLOSE_ACTIVITY_JOIN_REQUEST",
+                "args": { "user_id": user_id.into() },
+                "nonce": Uuid::new_v4().to_string(),
+            }),
+            1,
+        )
+    }
+
+    /// Reads the next frame off the IPC socket and, if it is one of the
+    /// activity invite events, parses it into a typed [`ActivityEvent`].
+    ///
+    /// Returns `Ok(None)` for frames that aren't a recognized activity
+    /// event (e.g. the ack for a `SUBSCRIBE` call).
+    fn try_next_activity_event(&mut self) -> Result<Option<ActivityEvent>, Box<dyn std::error::Error>> {
+        let (_, payload) = self.recv()?;
+
+        let evt = match payload.get("evt").and_then(|evt| evt.as_str()) {
+            Some(evt) => evt,
+            None => return Ok(None),
+        };
+        let data = payload.get("data").cloned().unwrap_or(serde_json::Value::Null);
+
+        let event = match evt {
+            ACTIVITY_JOIN => ActivityEvent::Join(serde_json::from_value(data)?),
+            ACTIVITY_SPECTATE => ActivityEvent::Spectate(serde_json::from_value(data)?),
+            ACTIVITY_JOIN_REQUEST => ActivityEvent::JoinRequest(serde_json::from_value(data)?),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(event))
+    }
+}
+
+impl<T: DiscordIpc> ActivityEvents for T {}
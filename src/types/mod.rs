@@ -0,0 +1,4 @@
+//! Types used to build and exchange Discord rich presence payloads.
+
+pub mod activity;
+pub mod events;